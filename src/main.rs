@@ -4,6 +4,8 @@ use std::fmt;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
+mod solver;
+
 trait Stackable {
     fn legal_push(&self, card: Card) -> bool;
     fn push(&mut self, card: Card);
@@ -11,6 +13,12 @@ trait Stackable {
     fn top(&self) -> Option<Card>;
 }
 
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum Colour {
+    Red,
+    Black,
+}
+
 #[derive(PartialEq, Eq, Clone, Copy, Debug, EnumIter)]
 enum Suit {
     Clubs,
@@ -18,6 +26,14 @@ enum Suit {
     Hearts,
     Spades,
 }
+impl Suit {
+    fn colour(&self) -> Colour {
+        match self {
+            Suit::Diamonds | Suit::Hearts => Colour::Red,
+            Suit::Clubs | Suit::Spades => Colour::Black,
+        }
+    }
+}
 impl fmt::Display for Suit {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -29,17 +45,31 @@ impl fmt::Display for Suit {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-struct Card {
-    suit: Suit,
-    rank: u8,
+// packed as suit (low 2 bits) | rank << 2, so a card is a single byte
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Card(u8);
+impl Card {
+    fn new(suit: Suit, rank: u8) -> Card {
+        Card((rank << 2) | suit as u8)
+    }
+    fn suit(&self) -> Suit {
+        match self.0 & 3 {
+            0 => Suit::Clubs,
+            1 => Suit::Diamonds,
+            2 => Suit::Hearts,
+            _ => Suit::Spades,
+        }
+    }
+    fn rank(&self) -> u8 {
+        self.0 >> 2
+    }
 }
 
 fn make_deck(suit: SuitIter, ranks: Vec<u8>) -> Vec<Card> {
     let mut cards = Vec::new();
     for rank in ranks {
         for suit in Suit::iter() {
-            cards.push(Card { suit, rank });
+            cards.push(Card::new(suit, rank));
         }
     }
     cards
@@ -59,20 +89,37 @@ fn deal_cards(cards: Vec<Card>, num_piles: usize) -> Vec<Vec<Card>> {
     piles
 }
 
-#[derive(Debug)]
+// selects which solitaire game `Game` is playing, since FreeCell and Klondike
+// share the same move engine but differ in layout and a few legality rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Variant {
+    Freecell,
+    Klondike,
+}
+
+#[derive(Debug, Clone)]
 struct Pile {
     cards: Vec<Card>,
+    variant: Variant,
 }
 impl Pile {
-    fn new(cards: Vec<Card>) -> Pile {
-        Pile { cards }
+    fn new(cards: Vec<Card>, variant: Variant) -> Pile {
+        Pile { cards, variant }
     }
 }
 impl Stackable for Pile {
     fn legal_push(&self, card: Card) -> bool {
         match self.cards.last() {
-            None => true,
-            Some(Card { suit, rank }) => card.suit != *suit && card.rank == rank - 1,
+            None => match self.variant {
+                // Klondike only allows a King to start a new column; FreeCell allows any card
+                Variant::Klondike => card.rank() == 13,
+                Variant::Freecell => true,
+            },
+            Some(top) => {
+                top.rank() > 1
+                    && card.suit().colour() != top.suit().colour()
+                    && card.rank() == top.rank() - 1
+            }
         }
     }
     fn push(&mut self, card: Card) {
@@ -87,6 +134,48 @@ impl Stackable for Pile {
     }
 }
 
+// the face-down draw pile; it is never a move target (`legal_push` always refuses),
+// so only `draw_stock` and its undo (`undo_draw`/`undo_recycle`) change it
+#[derive(Debug, Clone)]
+struct Stock {
+    cards: Vec<Card>,
+}
+impl Stackable for Stock {
+    fn legal_push(&self, _card: Card) -> bool {
+        false
+    }
+    fn push(&mut self, card: Card) {
+        self.cards.push(card);
+    }
+    fn pop(&mut self) -> Option<Card> {
+        self.cards.pop()
+    }
+    fn top(&self) -> Option<Card> {
+        self.cards.last().copied()
+    }
+}
+
+// the face-up pile the stock is drawn into; no move may land on it (`legal_push`
+// always refuses), so cards only arrive via `draw_stock` or when a move is undone
+#[derive(Debug, Clone)]
+struct Waste {
+    cards: Vec<Card>,
+}
+impl Stackable for Waste {
+    fn legal_push(&self, _card: Card) -> bool {
+        false
+    }
+    fn push(&mut self, card: Card) {
+        self.cards.push(card);
+    }
+    fn pop(&mut self) -> Option<Card> {
+        self.cards.pop()
+    }
+    fn top(&self) -> Option<Card> {
+        self.cards.last().copied()
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 struct Freecell {
     card: Option<Card>,
@@ -115,29 +204,25 @@ struct Foundation {
 impl Stackable for Foundation {
     fn legal_push(&self, card: Card) -> bool {
         match self.card {
-            None => card.rank == 0,
-            Some(Card { suit, rank }) => card.suit == suit && card.rank == rank + 1,
+            None => card.rank() == 1,
+            Some(top) => card.suit() == top.suit() && card.rank() == top.rank() + 1,
         }
     }
     fn push(&mut self, card: Card) {
         self.card = Some(card);
     }
     fn pop(&mut self) -> Option<Card> {
-        // return the card and decrement the rank by 1
-        match self.card {
-            None => None,
-            Some(Card { suit, rank }) => {
-                if rank == 1 {
-                    self.card = None;
-                } else {
-                    self.card = Some(Card {
-                        suit,
-                        rank: rank - 1,
-                    });
-                }
-                self.card
-            }
-        }
+        // removes and returns the card that's actually on top; the foundation only
+        // ever stores its current top rank, so the card one rank below (which must
+        // already be on the foundation, since a foundation only grows sequentially)
+        // becomes the new top rather than the foundation going empty
+        let top = self.card?;
+        self.card = if top.rank() == 1 {
+            None
+        } else {
+            Some(Card::new(top.suit(), top.rank() - 1))
+        };
+        Some(top)
     }
     fn top(&self) -> Option<Card> {
         self.card
@@ -147,12 +232,16 @@ impl Stackable for Foundation {
 const FREECELL_NUM: usize = 4;
 const FOUNDATION_NUM: usize = 4; // TODO: make this automatically infered from Suit
 const TABLEAU_NUM: usize = 8;
+const KLONDIKE_TABLEAU_NUM: usize = 7;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Game {
+    variant: Variant,
     tableau: Vec<Pile>,
     freecells: [Freecell; FREECELL_NUM],
     foundations: [Foundation; FOUNDATION_NUM],
+    stock: Option<Stock>,
+    waste: Option<Waste>,
 }
 impl Game {
     fn new() -> Game {
@@ -161,16 +250,319 @@ impl Game {
         cards.shuffle(&mut rng);
         let tableau = deal_cards(cards, TABLEAU_NUM)
             .into_iter()
-            .map(|cards| Pile::new(cards))
+            .map(|cards| Pile::new(cards, Variant::Freecell))
             .collect();
         let freecells = [Freecell { card: None }; FREECELL_NUM];
         let foundations = [Foundation { card: None }; FOUNDATION_NUM];
         Game {
+            variant: Variant::Freecell,
             tableau,
             freecells,
             foundations,
+            stock: None,
+            waste: None,
         }
     }
+
+    // the classic Klondike deal: seven columns holding 1..=7 cards, the rest face
+    // down in the stock
+    // known limitation: this board has no face-down/face-up concept anywhere, so
+    // every dealt tableau card is rendered face-up from the start (see `draw_game`)
+    // rather than only the top card of each column. That makes the deal easier to
+    // read than real Klondike, not just a rendering nitpick; tracking per-card
+    // visibility is future work.
+    fn new_klondike() -> Game {
+        let mut rng = thread_rng();
+        let mut cards = make_deck(Suit::iter(), (1..=13).collect());
+        cards.shuffle(&mut rng);
+
+        let mut tableau: Vec<Pile> = (0..KLONDIKE_TABLEAU_NUM)
+            .map(|_| Pile::new(Vec::new(), Variant::Klondike))
+            .collect();
+        for (i, pile) in tableau.iter_mut().enumerate() {
+            for _ in 0..=i {
+                pile.cards.push(cards.pop().unwrap());
+            }
+        }
+
+        let freecells = [Freecell { card: None }; FREECELL_NUM];
+        let foundations = [Foundation { card: None }; FOUNDATION_NUM];
+        Game {
+            variant: Variant::Klondike,
+            tableau,
+            freecells,
+            foundations,
+            stock: Some(Stock { cards }),
+            waste: Some(Waste { cards: Vec::new() }),
+        }
+    }
+
+    // reproduces the classic Microsoft FreeCell deal numbering via its 31-bit LCG,
+    // so a given seed always yields the same deal
+    fn new_seeded(seed: u32) -> Game {
+        let mut state: u32 = seed;
+        let mut rand = move || {
+            state = (state.wrapping_mul(214013).wrapping_add(2531011)) & 0x7fff_ffff;
+            (state >> 16) & 0x7fff
+        };
+
+        let mut deck: [u8; 52] = [0; 52];
+        for (i, card) in deck.iter_mut().enumerate() {
+            *card = i as u8;
+        }
+
+        let mut tableau_cards: Vec<Vec<Card>> = (0..TABLEAU_NUM).map(|_| Vec::new()).collect();
+        for i in 0..52 {
+            let pos = rand() as usize % (52 - i);
+            let card = deck[pos];
+            deck[pos] = deck[52 - i - 1];
+
+            let suit = match card % 4 {
+                0 => Suit::Clubs,
+                1 => Suit::Diamonds,
+                2 => Suit::Hearts,
+                _ => Suit::Spades,
+            };
+            let rank = card / 4 + 1;
+            tableau_cards[i % TABLEAU_NUM].push(Card::new(suit, rank));
+        }
+
+        let tableau = tableau_cards
+            .into_iter()
+            .map(|cards| Pile::new(cards, Variant::Freecell))
+            .collect();
+        let freecells = [Freecell { card: None }; FREECELL_NUM];
+        let foundations = [Foundation { card: None }; FOUNDATION_NUM];
+        Game {
+            variant: Variant::Freecell,
+            tableau,
+            freecells,
+            foundations,
+            stock: None,
+            waste: None,
+        }
+    }
+
+    // a stable, human-editable text snapshot of the full board: freecells (or the
+    // stock/waste for Klondike), foundation tops, then each tableau column
+    fn serialize(&self) -> String {
+        let mut out = String::new();
+        out.push_str(match self.variant {
+            Variant::Freecell => "Variant: Freecell\n",
+            Variant::Klondike => "Variant: Klondike\n",
+        });
+
+        match self.variant {
+            Variant::Freecell => {
+                out.push_str("Freecells:");
+                for cell in &self.freecells {
+                    out.push(' ');
+                    out.push_str(&card_token(cell.top()));
+                }
+                out.push('\n');
+            }
+            Variant::Klondike => {
+                out.push_str("Stock:");
+                for card in &self.stock.as_ref().unwrap().cards {
+                    out.push(' ');
+                    out.push_str(&card_token(Some(*card)));
+                }
+                out.push('\n');
+                out.push_str("Waste:");
+                for card in &self.waste.as_ref().unwrap().cards {
+                    out.push(' ');
+                    out.push_str(&card_token(Some(*card)));
+                }
+                out.push('\n');
+            }
+        }
+
+        out.push_str("Foundations:");
+        for foundation in &self.foundations {
+            out.push(' ');
+            out.push_str(&card_token(foundation.top()));
+        }
+        out.push('\n');
+
+        for (i, pile) in self.tableau.iter().enumerate() {
+            out.push_str(&format!("{}:", i + 1));
+            for card in &pile.cards {
+                out.push(' ');
+                out.push_str(&card_token(Some(*card)));
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+
+    // the inverse of `serialize`; `Err(())` on malformed lines or a board that doesn't
+    // hold exactly one of every card. Column labels are not checked: columns are
+    // taken in line order
+    fn deserialize(text: &str) -> Result<Game, ()> {
+        let mut lines = text.lines();
+
+        let variant = match lines.next().ok_or(())?.strip_prefix("Variant: ").ok_or(())? {
+            "Freecell" => Variant::Freecell,
+            "Klondike" => Variant::Klondike,
+            _ => return Err(()),
+        };
+
+        let mut freecells = [Freecell { card: None }; FREECELL_NUM];
+        let mut stock = None;
+        let mut waste = None;
+
+        match variant {
+            Variant::Freecell => {
+                let tokens = lines.next().ok_or(())?.strip_prefix("Freecells:").ok_or(())?;
+                let cards = parse_cards(tokens)?;
+                if cards.len() != FREECELL_NUM {
+                    return Err(());
+                }
+                for (cell, card) in freecells.iter_mut().zip(cards) {
+                    cell.card = card;
+                }
+            }
+            Variant::Klondike => {
+                let tokens = lines.next().ok_or(())?.strip_prefix("Stock:").ok_or(())?;
+                let cards = parse_cards(tokens)?
+                    .into_iter()
+                    .map(|c| c.ok_or(()))
+                    .collect::<Result<Vec<Card>, ()>>()?;
+                stock = Some(Stock { cards });
+
+                let tokens = lines.next().ok_or(())?.strip_prefix("Waste:").ok_or(())?;
+                let cards = parse_cards(tokens)?
+                    .into_iter()
+                    .map(|c| c.ok_or(()))
+                    .collect::<Result<Vec<Card>, ()>>()?;
+                waste = Some(Waste { cards });
+            }
+        }
+
+        let tokens = lines.next().ok_or(())?.strip_prefix("Foundations:").ok_or(())?;
+        let cards = parse_cards(tokens)?;
+        if cards.len() != FOUNDATION_NUM {
+            return Err(());
+        }
+        let mut foundations = [Foundation { card: None }; FOUNDATION_NUM];
+        for (foundation, card) in foundations.iter_mut().zip(cards) {
+            foundation.card = card;
+        }
+
+        let mut tableau = Vec::new();
+        for line in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (_, tokens) = line.split_once(':').ok_or(())?;
+            let cards = parse_cards(tokens)?
+                .into_iter()
+                .map(|c| c.ok_or(()))
+                .collect::<Result<Vec<Card>, ()>>()?;
+            tableau.push(Pile::new(cards, variant));
+        }
+
+        let game = Game {
+            variant,
+            tableau,
+            freecells,
+            foundations,
+            stock,
+            waste,
+        };
+        if !is_complete_deck(&game) {
+            return Err(());
+        }
+        Ok(game)
+    }
+}
+
+// reconstructs every card implied by a board, so a hand-edited save can be checked
+// against a full deck: tableau/freecell/stock/waste cards directly, and each
+// foundation's top rank expanded down to its Ace (foundations only store the top card)
+fn all_cards(game: &Game) -> Vec<Card> {
+    let mut cards = Vec::new();
+    for pile in &game.tableau {
+        cards.extend(pile.cards.iter().copied());
+    }
+    for cell in &game.freecells {
+        cards.extend(cell.top());
+    }
+    for foundation in &game.foundations {
+        if let Some(top) = foundation.top() {
+            for rank in 1..=top.rank() {
+                cards.push(Card::new(top.suit(), rank));
+            }
+        }
+    }
+    if let Some(stock) = &game.stock {
+        cards.extend(stock.cards.iter().copied());
+    }
+    if let Some(waste) = &game.waste {
+        cards.extend(waste.cards.iter().copied());
+    }
+    cards
+}
+
+// a deserialized board is only sound if it accounts for exactly one of every card
+fn is_complete_deck(game: &Game) -> bool {
+    let cards = all_cards(game);
+    if cards.len() != 52 {
+        return false;
+    }
+    let mut seen = [false; 52];
+    for card in cards {
+        let idx = card.suit() as usize * 13 + (card.rank() - 1) as usize;
+        if seen[idx] {
+            return false;
+        }
+        seen[idx] = true;
+    }
+    true
+}
+
+// renders a card as a stable two-character token: rank then suit glyph, or "--"
+fn card_token(card: Option<Card>) -> String {
+    match card {
+        None => "--".to_string(),
+        Some(card) => format!("{}{}", rank_to_char(card.rank()), card.suit()),
+    }
+}
+
+fn parse_cards(tokens: &str) -> Result<Vec<Option<Card>>, ()> {
+    tokens.split_whitespace().map(parse_card_token).collect()
+}
+
+fn parse_card_token(token: &str) -> Result<Option<Card>, ()> {
+    if token == "--" {
+        return Ok(None);
+    }
+    let mut chars = token.chars();
+    let rank = char_to_rank(chars.next().ok_or(())?)?;
+    let suit = match chars.next().ok_or(())? {
+        '♣' => Suit::Clubs,
+        '♦' => Suit::Diamonds,
+        '♥' => Suit::Hearts,
+        '♠' => Suit::Spades,
+        _ => return Err(()),
+    };
+    if chars.next().is_some() {
+        return Err(());
+    }
+    Ok(Some(Card::new(suit, rank)))
+}
+
+fn char_to_rank(c: char) -> Result<u8, ()> {
+    match c {
+        'A' => Ok(1),
+        'T' => Ok(10),
+        'J' => Ok(11),
+        'Q' => Ok(12),
+        'K' => Ok(13),
+        '2'..='9' => Ok(c as u8 - 48),
+        _ => Err(()),
+    }
 }
 
 // ACUTAL LOOP
@@ -238,7 +630,8 @@ fn draw_card(stdout: &mut io::Stdout, card: Option<Card>, x: u16, y: u16) {
     draw_card_frame(&mut stdout, x, y);
     match card {
         None => {}
-        Some(Card { suit, rank }) => {
+        Some(card) => {
+            let (suit, rank) = (card.suit(), card.rank());
             if suit == Suit::Diamonds || suit == Suit::Hearts {
                 queue!(stdout, crossterm::style::SetForegroundColor(Color::Red));
             };
@@ -265,14 +658,33 @@ fn draw_game(game: &Game) {
     let pile_origin = (origin.0, origin.1 + 6);
     let mut stdout = io::stdout();
     queue!(stdout, Clear(All));
-    // drawing the freecells
-    for (i, freecell) in game.freecells.iter().enumerate() {
-        draw_card(
-            &mut stdout,
-            freecell.card,
-            origin.0 + 6 * i as u16,
-            origin.1,
-        );
+    // the freecells (FreeCell) and the stock/waste (Klondike) share this slot,
+    // since a game only ever uses one or the other
+    match game.variant {
+        Variant::Freecell => {
+            for (i, freecell) in game.freecells.iter().enumerate() {
+                draw_card(
+                    &mut stdout,
+                    freecell.card,
+                    origin.0 + 6 * i as u16,
+                    origin.1,
+                );
+            }
+        }
+        Variant::Klondike => {
+            draw_card(
+                &mut stdout,
+                game.stock.as_ref().and_then(|s| s.top()),
+                origin.0,
+                origin.1,
+            );
+            draw_card(
+                &mut stdout,
+                game.waste.as_ref().and_then(|w| w.top()),
+                origin.0 + 6,
+                origin.1,
+            );
+        }
     }
     // draw the foundations
     for (i, foundation) in game.foundations.iter().enumerate() {
@@ -327,29 +739,207 @@ fn move_card(game: &mut Game, from: char, to: char) -> Result<(), ()> {
     }
 }
 
+// what `draw_stock` actually did, so the caller can record a history entry that
+// `undo_draw`/`undo_recycle` can reverse precisely
+enum DrawOutcome {
+    Drew,
+    Recycled,
+}
+
+// draws the top of the stock onto the waste (Klondike only); once the stock runs
+// out, recycles the waste back into the stock in the same draw order
+fn draw_stock(game: &mut Game) -> Result<DrawOutcome, ()> {
+    if game.variant != Variant::Klondike {
+        return Err(());
+    }
+    if let Some(card) = game.stock.as_mut().ok_or(())?.pop() {
+        game.waste.as_mut().ok_or(())?.push(card);
+        return Ok(DrawOutcome::Drew);
+    }
+
+    let waste = game.waste.as_mut().ok_or(())?;
+    if waste.cards.is_empty() {
+        return Err(());
+    }
+    let mut recycled = std::mem::take(&mut waste.cards);
+    recycled.reverse();
+    game.stock.as_mut().unwrap().cards = recycled;
+    Ok(DrawOutcome::Recycled)
+}
+
+// reverses a single `draw_stock` draw: moves the card back from the waste to the stock
+fn undo_draw(game: &mut Game) -> Result<(), ()> {
+    let card = game.waste.as_mut().ok_or(())?.pop().ok_or(())?;
+    game.stock.as_mut().ok_or(())?.push(card);
+    Ok(())
+}
+
+// reverses a `draw_stock` recycle: the stock currently holds the waste reversed, so
+// reversing it again restores the original waste order
+fn undo_recycle(game: &mut Game) -> Result<(), ()> {
+    let mut restored = std::mem::take(&mut game.stock.as_mut().ok_or(())?.cards);
+    restored.reverse();
+    game.waste.as_mut().ok_or(())?.cards = restored;
+    Ok(())
+}
+
 const FOUNDATION_KEYS: [char; 4] = ['t', 'y', 'u', 'i'];
 const FREECELL_KEYS: [char; 4] = ['q', 'w', 'e', 'r'];
 const PILE_KEYS: [char; 8] = ['1', '2', '3', '4', '5', '6', '7', '8'];
+const WASTE_KEY: char = 'f';
+const SAVE_FILE: &str = "save.txt";
 
-// using a char and the game, get the corresponding stackable
+// a successfully-applied action, recorded so it can be undone or redone later. Most
+// actions are a Slide between two addressable stacks; a stock draw is split into
+// Draw/Recycle so undo knows which of `draw_stock`'s two behaviours to reverse
+#[derive(Debug, Clone, Copy)]
+enum Move {
+    Slide { from: char, to: char, count: usize },
+    Draw,
+    Recycle,
+}
+
+// (re-)applies a recorded move; the main loop uses it to redo an undone move
+fn apply_move(game: &mut Game, record: Move) -> Result<(), ()> {
+    match record {
+        Move::Slide { from, to, count: 1 } => move_card(game, from, to),
+        Move::Slide { from, to, count } => move_sequence(game, from, to, count),
+        // the game is back in the state it was in right before the original draw,
+        // so `draw_stock` takes the same branch (draw vs. recycle) it took then
+        Move::Draw | Move::Recycle => draw_stock(game).map(|_| ()),
+    }
+}
+
+// reverses a previously-applied move by moving the same cards straight back, without
+// the legality checks a fresh move gets: the cards sat exactly there before the move
+// being undone was made, even where that spot would not accept them as a new move
+fn undo_move(game: &mut Game, record: Move) -> Result<(), ()> {
+    match record {
+        Move::Slide { from, to, count } => {
+            let to_stack = get_stackable(game, to)?;
+            let mut buffer = Vec::with_capacity(count);
+            for _ in 0..count {
+                buffer.push(to_stack.pop().ok_or(())?);
+            }
+            let from_stack = get_stackable(game, from)?;
+            for card in buffer.into_iter().rev() {
+                from_stack.push(card);
+            }
+            Ok(())
+        }
+        Move::Draw => undo_draw(game),
+        Move::Recycle => undo_recycle(game),
+    }
+}
+
+// using a char and the game, get the corresponding stackable; the variant decides
+// which keys are addressable at all (e.g. freecells only exist in FreeCell, the
+// waste only in Klondike). The stock is deliberately not addressable here: it can
+// only be dealt from via `draw_stock`, never used as a generic move source/target.
 fn get_stackable(game: &mut Game, key: char) -> Result<&mut dyn Stackable, ()> {
     if FOUNDATION_KEYS.contains(&key) {
         Ok(&mut game.foundations[FOUNDATION_KEYS.iter().position(|&x| x == key).unwrap()])
-    } else if FREECELL_KEYS.contains(&key) {
+    } else if game.variant == Variant::Freecell && FREECELL_KEYS.contains(&key) {
         Ok(&mut game.freecells[FREECELL_KEYS.iter().position(|&x| x == key).unwrap()])
-    } else if PILE_KEYS.contains(&key) {
-        Ok(&mut game.tableau[PILE_KEYS.iter().position(|&x| x == key).unwrap()])
+    } else if let Some(idx) = pile_index(key) {
+        game.tableau.get_mut(idx).map(|p| p as &mut dyn Stackable).ok_or(())
+    } else if game.variant == Variant::Klondike && key == WASTE_KEY {
+        game.waste.as_mut().map(|w| w as &mut dyn Stackable).ok_or(())
     } else {
         Err(())
     }
+}
+
+// the index into `game.tableau` addressed by a tableau key, if `key` is one
+fn pile_index(key: char) -> Option<usize> {
+    PILE_KEYS.iter().position(|&x| x == key)
+}
+
+// moves the top `count` cards out of the tableau pile addressed by `from` onto the
+// tableau pile addressed by `to`, treating them as a single descending,
+// alternating-colour run (a FreeCell "supermove")
+fn move_sequence(game: &mut Game, from: char, to: char, count: usize) -> Result<(), ()> {
+    let from_idx = pile_index(from).ok_or(())?;
+    let to_idx = pile_index(to).ok_or(())?;
+    if from_idx == to_idx || count == 0 {
+        return Err(());
+    }
+    if from_idx >= game.tableau.len() || to_idx >= game.tableau.len() {
+        return Err(());
+    }
+
+    let from_len = game.tableau[from_idx].cards.len();
+    if count > from_len {
+        return Err(());
+    }
+
+    // the moved cards must themselves form a legal run: descending rank, alternating colour
+    let run = &game.tableau[from_idx].cards[from_len - count..];
+    for pair in run.windows(2) {
+        let (lower, upper) = (pair[0], pair[1]);
+        if lower.rank() == 1
+            || lower.suit().colour() == upper.suit().colour()
+            || upper.rank() != lower.rank() - 1
+        {
+            return Err(());
+        }
+    }
+
+    // the classic FreeCell resource formula: each empty freecell doubles the movable
+    // count, as does each empty column other than the one being landed on. Freecells
+    // aren't reachable at all in Klondike, so they never contribute there.
+    let free_cells_empty = match game.variant {
+        Variant::Freecell => game.freecells.iter().filter(|f| f.top().is_none()).count(),
+        Variant::Klondike => 0,
+    };
+    let empty_columns = game
+        .tableau
+        .iter()
+        .enumerate()
+        .filter(|&(i, pile)| i != to_idx && pile.cards.is_empty())
+        .count();
+    let max_count = (1 + free_cells_empty) * 2usize.pow(empty_columns as u32);
+    if count > max_count {
+        return Err(());
+    }
+
+    let bottom_of_run = run[0];
+    if !game.tableau[to_idx].legal_push(bottom_of_run) {
+        return Err(());
+    }
+
+    // everything above is checked up front, so the pops/pushes below cannot fail
+    // partway through and leave the board in a corrupted state
+    let mut buffer = Vec::with_capacity(count);
+    for _ in 0..count {
+        buffer.push(game.tableau[from_idx].pop().unwrap());
+    }
+    for card in buffer.into_iter().rev() {
+        game.tableau[to_idx].push(card);
+    }
+    Ok(())
+}
 
 fn main() {
     let _clean_up = CleanUp;
 
+    // an optional argument selects the variant: `solitaire klondike` starts a
+    // Klondike game, a number (e.g. `solitaire 11982`) replays that FreeCell deal
+    let arg = std::env::args().nth(1);
+
     enter_alt_screen();
-    let mut game = Game::new();
+    let mut game = match arg.as_deref() {
+        Some("klondike") => Game::new_klondike(),
+        Some(arg) => match arg.parse() {
+            Ok(seed) => Game::new_seeded(seed),
+            Err(_) => Game::new(),
+        },
+        None => Game::new(),
+    };
     draw_game(&game);
     let mut stdin = io::stdin();
+    let mut history: Vec<Move> = Vec::new();
+    let mut redo_stack: Vec<Move> = Vec::new();
     loop {
         let mut buffer = [0; 1];
         stdin.read_exact(&mut buffer).unwrap();
@@ -359,6 +949,75 @@ fn main() {
         if key == 'Q' {
             break;
         }
+        // undo the last move ('u') or replay it ('Ctrl-R')
+        if key == 'u' {
+            if let Some(record) = history.pop() {
+                if undo_move(&mut game, record).is_ok() {
+                    redo_stack.push(record);
+                    draw_game(&game);
+                } else {
+                    history.push(record);
+                }
+            }
+            continue;
+        }
+        if key == '\u{12}' {
+            if let Some(record) = redo_stack.pop() {
+                if apply_move(&mut game, record).is_ok() {
+                    history.push(record);
+                    draw_game(&game);
+                } else {
+                    redo_stack.push(record);
+                }
+            }
+            continue;
+        }
+        // ask the solver whether the current position is solvable
+        if key == 'S' {
+            // the solver only ever moves between tableau, freecells and foundations;
+            // it has no notion of Stock/Waste, so it can't yet evaluate a Klondike
+            // game (it would wrongly call anything still in the stock unreachable)
+            let message = if game.variant != Variant::Freecell {
+                "Solver only supports FreeCell so far".to_string()
+            } else {
+                match solver::solve(game.clone(), solver::Budget::default()) {
+                    solver::SolveResult::Solved(moves) => format!("Solvable in {} moves", moves.len()),
+                    solver::SolveResult::Unsolvable => "Unsolvable".to_string(),
+                    solver::SolveResult::Unknown => "Unknown (budget exceeded)".to_string(),
+                }
+            };
+            execute!(io::stdout(), MoveTo(0, 25), Print(message));
+            continue;
+        }
+        // draw from the stock (Klondike only); recorded on `history` like any other
+        // move so 'u'/Ctrl-R can undo/redo it
+        if key == 'D' {
+            if let Ok(outcome) = draw_stock(&mut game) {
+                history.push(match outcome {
+                    DrawOutcome::Drew => Move::Draw,
+                    DrawOutcome::Recycled => Move::Recycle,
+                });
+                redo_stack.clear();
+                draw_game(&game);
+            }
+            continue;
+        }
+        // save to / load from the fixed save file
+        if key == 'W' {
+            let _ = std::fs::write(SAVE_FILE, game.serialize());
+            continue;
+        }
+        if key == 'L' {
+            if let Ok(text) = std::fs::read_to_string(SAVE_FILE) {
+                if let Ok(loaded) = Game::deserialize(&text) {
+                    game = loaded;
+                    history.clear();
+                    redo_stack.clear();
+                    draw_game(&game);
+                }
+            }
+            continue;
+        }
         // do a move
         if key == ' ' {
             let mut buffer = [0; 2];
@@ -373,10 +1032,247 @@ fn main() {
             if from == to {
                 continue;
             }
-            // try to move the card
-            if move_card(&mut game, from, to).is_ok() {
+            // try to move a single card, falling back to a supermove of the
+            // longest legal run between two tableau columns
+            let applied = if move_card(&mut game, from, to).is_ok() {
+                Some(Move::Slide { from, to, count: 1 })
+            } else if let Some(longest_run) = pile_index(from)
+                .and_then(|idx| game.tableau.get(idx))
+                .map(|pile| pile.cards.len())
+            {
+                (2..=longest_run)
+                    .rev()
+                    .find(|&count| move_sequence(&mut game, from, to, count).is_ok())
+                    .map(|count| Move::Slide { from, to, count })
+            } else {
+                None
+            };
+
+            if let Some(record) = applied {
+                history.push(record);
+                redo_stack.clear();
                 draw_game(&game);
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn single_pile_game(cards: Vec<Card>) -> Game {
+        Game {
+            variant: Variant::Freecell,
+            tableau: vec![Pile::new(cards, Variant::Freecell), Pile::new(Vec::new(), Variant::Freecell)],
+            freecells: [Freecell { card: None }; FREECELL_NUM],
+            foundations: [Foundation { card: None }; FOUNDATION_NUM],
+            stock: None,
+            waste: None,
+        }
+    }
+
+    #[test]
+    fn foundation_pop_returns_the_card_that_was_pushed() {
+        let mut foundation = Foundation { card: None };
+        foundation.push(Card::new(Suit::Clubs, 1));
+        foundation.push(Card::new(Suit::Clubs, 2));
+        assert_eq!(foundation.pop(), Some(Card::new(Suit::Clubs, 2)));
+        assert_eq!(foundation.top(), Some(Card::new(Suit::Clubs, 1)));
+    }
+
+    #[test]
+    fn undo_after_a_foundation_move_restores_the_original_card() {
+        // Two at the bottom, Ace on top of tableau pile '1'
+        let mut game = single_pile_game(vec![Card::new(Suit::Clubs, 2), Card::new(Suit::Clubs, 1)]);
+
+        apply_move(&mut game, Move::Slide { from: '1', to: 't', count: 1 }).unwrap(); // Ace -> foundation
+        apply_move(&mut game, Move::Slide { from: '1', to: 't', count: 1 }).unwrap(); // Two -> foundation
+        undo_move(&mut game, Move::Slide { from: '1', to: 't', count: 1 }).unwrap(); // undo the Two
+
+        assert_eq!(game.tableau[0].top(), Some(Card::new(Suit::Clubs, 2)));
+        assert_eq!(game.foundations[0].top(), Some(Card::new(Suit::Clubs, 1)));
+    }
+
+    #[test]
+    fn serialize_then_deserialize_round_trips() {
+        let game = Game::new_seeded(1);
+        let text = game.serialize();
+        let reloaded = Game::deserialize(&text).unwrap();
+        assert_eq!(reloaded.serialize(), text);
+    }
+
+    // pins `Game::new_seeded` against its own deterministic output, so a change to
+    // the LCG/dealing arithmetic shows up as a failing test rather than silently
+    // reshuffling every seed's deal
+    #[test]
+    fn new_seeded_deal_is_pinned_to_its_known_output() {
+        let game = Game::new_seeded(1);
+        let expected = "Variant: Freecell\n\
+Freecells: -- -- -- --\n\
+Foundations: -- -- -- --\n\
+1: J♦ K♦ 2♠ 4♣ 3♠ 6♦ 6♠\n\
+2: 2♦ K♣ K♠ 5♣ T♦ 8♠ 9♣\n\
+3: 9♥ 9♠ 9♦ T♠ 4♠ 8♦ 2♥\n\
+4: J♣ 5♠ Q♦ Q♥ T♥ Q♠ 6♥\n\
+5: 5♦ A♦ J♠ 4♥ 8♥ 6♣\n\
+6: 7♥ Q♣ A♠ A♣ 2♣ 3♦\n\
+7: 7♣ K♥ A♥ 4♦ J♥ 8♣\n\
+8: 5♥ 3♥ 3♣ 7♠ 7♦ T♣\n";
+        assert_eq!(game.serialize(), expected);
+    }
+
+    // builds a Freecell game with an arbitrary tableau/freecell layout, for tests
+    // that don't care about foundations/stock/waste
+    fn game_with(tableau: Vec<Vec<Card>>, freecells_filled: usize) -> Game {
+        let mut freecells = [Freecell { card: None }; FREECELL_NUM];
+        for cell in freecells.iter_mut().take(freecells_filled) {
+            *cell = Freecell { card: Some(Card::new(Suit::Hearts, 1)) };
+        }
+        Game {
+            variant: Variant::Freecell,
+            tableau: tableau.into_iter().map(|cards| Pile::new(cards, Variant::Freecell)).collect(),
+            freecells,
+            foundations: [Foundation { card: None }; FOUNDATION_NUM],
+            stock: None,
+            waste: None,
+        }
+    }
+
+    #[test]
+    fn supermove_relocates_the_whole_run_in_order() {
+        let mut game = game_with(
+            vec![
+                vec![
+                    Card::new(Suit::Spades, 6),
+                    Card::new(Suit::Hearts, 5),
+                    Card::new(Suit::Clubs, 4),
+                ],
+                Vec::new(),
+            ],
+            0,
+        );
+        move_sequence(&mut game, '1', '2', 3).unwrap();
+        assert!(game.tableau[0].cards.is_empty());
+        assert_eq!(
+            game.tableau[1].cards,
+            vec![
+                Card::new(Suit::Spades, 6),
+                Card::new(Suit::Hearts, 5),
+                Card::new(Suit::Clubs, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn supermove_rejects_a_run_that_is_not_descending_and_alternating() {
+        // same colour, not a legal run
+        let mut game = game_with(
+            vec![vec![Card::new(Suit::Spades, 6), Card::new(Suit::Clubs, 5)], Vec::new()],
+            0,
+        );
+        assert_eq!(move_sequence(&mut game, '1', '2', 2), Err(()));
+    }
+
+    #[test]
+    fn supermove_respects_the_freecell_and_empty_column_resource_cap() {
+        // no empty freecells, no empty columns: cap is (1 + 0) * 2^0 == 1
+        let mut game = game_with(
+            vec![
+                vec![Card::new(Suit::Hearts, 6), Card::new(Suit::Clubs, 5)],
+                vec![Card::new(Suit::Clubs, 7)],
+            ],
+            FREECELL_NUM,
+        );
+        assert_eq!(move_sequence(&mut game.clone(), '1', '2', 2), Err(()));
+
+        // freeing one freecell doubles the cap to 2, which is now enough
+        game.freecells[0] = Freecell { card: None };
+        move_sequence(&mut game, '1', '2', 2).unwrap();
+        assert_eq!(
+            game.tableau[1].cards,
+            vec![
+                Card::new(Suit::Clubs, 7),
+                Card::new(Suit::Hearts, 6),
+                Card::new(Suit::Clubs, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn new_klondike_deals_one_through_seven_cards_across_seven_columns() {
+        let game = Game::new_klondike();
+        assert_eq!(game.tableau.len(), KLONDIKE_TABLEAU_NUM);
+        for (i, pile) in game.tableau.iter().enumerate() {
+            assert_eq!(pile.cards.len(), i + 1);
+        }
+        let dealt: usize = game.tableau.iter().map(|p| p.cards.len()).sum();
+        assert_eq!(game.stock.unwrap().cards.len(), 52 - dealt);
+        assert_eq!(game.waste.unwrap().cards.len(), 0);
+    }
+
+    #[test]
+    fn klondike_empty_column_only_accepts_a_king() {
+        let empty = Pile::new(Vec::new(), Variant::Klondike);
+        assert!(empty.legal_push(Card::new(Suit::Hearts, 13)));
+        assert!(!empty.legal_push(Card::new(Suit::Hearts, 12)));
+    }
+
+    #[test]
+    fn draw_stock_cycles_through_the_stock_then_recycles_the_waste() {
+        let mut game = Game {
+            variant: Variant::Klondike,
+            tableau: Vec::new(),
+            freecells: [Freecell { card: None }; FREECELL_NUM],
+            foundations: [Foundation { card: None }; FOUNDATION_NUM],
+            stock: Some(Stock {
+                cards: vec![Card::new(Suit::Clubs, 1), Card::new(Suit::Diamonds, 2)],
+            }),
+            waste: Some(Waste { cards: Vec::new() }),
+        };
+
+        draw_stock(&mut game).unwrap();
+        assert_eq!(game.waste.as_ref().unwrap().cards, vec![Card::new(Suit::Diamonds, 2)]);
+        assert_eq!(game.stock.as_ref().unwrap().cards, vec![Card::new(Suit::Clubs, 1)]);
+
+        draw_stock(&mut game).unwrap();
+        assert!(game.stock.as_ref().unwrap().cards.is_empty());
+        assert_eq!(
+            game.waste.as_ref().unwrap().cards,
+            vec![Card::new(Suit::Diamonds, 2), Card::new(Suit::Clubs, 1)]
+        );
+
+        // stock is empty: this draw recycles the waste back into the stock instead
+        draw_stock(&mut game).unwrap();
+        assert!(game.waste.as_ref().unwrap().cards.is_empty());
+        assert_eq!(
+            game.stock.as_ref().unwrap().cards,
+            vec![Card::new(Suit::Clubs, 1), Card::new(Suit::Diamonds, 2)]
+        );
+    }
+
+    #[test]
+    fn waste_rejects_any_push() {
+        let waste = Waste { cards: Vec::new() };
+        assert!(!waste.legal_push(Card::new(Suit::Hearts, 1)));
+    }
+
+    #[test]
+    fn undo_after_a_draw_puts_the_card_back_on_the_stock() {
+        let mut game = Game {
+            variant: Variant::Klondike,
+            tableau: Vec::new(),
+            freecells: [Freecell { card: None }; FREECELL_NUM],
+            foundations: [Foundation { card: None }; FOUNDATION_NUM],
+            stock: Some(Stock { cards: vec![Card::new(Suit::Clubs, 1)] }),
+            waste: Some(Waste { cards: Vec::new() }),
+        };
+
+        apply_move(&mut game, Move::Draw).unwrap();
+        assert_eq!(game.waste.as_ref().unwrap().cards, vec![Card::new(Suit::Clubs, 1)]);
+
+        undo_move(&mut game, Move::Draw).unwrap();
+        assert!(game.waste.as_ref().unwrap().cards.is_empty());
+        assert_eq!(game.stock.as_ref().unwrap().cards, vec![Card::new(Suit::Clubs, 1)]);
+    }
+}